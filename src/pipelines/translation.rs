@@ -27,7 +27,7 @@
 //!# use rust_bert::pipelines::generation::LanguageGenerator;
 //! use rust_bert::pipelines::translation::{TranslationModel, TranslationConfig, Language};
 //! use tch::Device;
-//! let mut translation_config =  TranslationConfig::new(Language::EnglishToFrench, Device::cuda_if_available());
+//! let mut translation_config =  TranslationConfig::new(Language::English, Language::French, Device::cuda_if_available())?;
 //! let mut model = TranslationModel::new(translation_config)?;
 //!
 //! let input = ["This is a sentence to be translated"];
@@ -46,15 +46,131 @@
 
 use crate::pipelines::generation::{MarianGenerator, GenerateConfig, LanguageGenerator};
 use tch::Device;
+#[cfg(test)]
+use tch::Tensor;
 use crate::common::resources::{Resource, RemoteResource};
 use crate::marian::{MarianModelResources, MarianConfigResources, MarianVocabResources, MarianSpmResources};
 
-/// Pretrained languages available for direct use
+/// Languages that can be used as either the source or the target of an Opus-MT translation pair.
+///
+/// This covers the individual languages commonly published by the Helsinki-NLP group, as well as
+/// the `Romance` pseudo-language used to select one of their many-to-many multilingual checkpoints
+/// (e.g. `opus-mt-en-ROMANCE`, which translates English into a target chosen among the Romance
+/// languages at call time - see [`TranslationConfig::target_language`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Language {
-    EnglishToFrench,
-    FrenchToEnglish,
+    English,
+    French,
+    German,
+    Spanish,
+    Portuguese,
+    Italian,
+    Catalan,
+    Romanian,
+    Dutch,
+    Swedish,
+    Danish,
+    Norwegian,
+    Finnish,
+    Russian,
+    Polish,
+    Czech,
+    Ukrainian,
+    Greek,
+    Turkish,
+    Arabic,
+    Hebrew,
+    Hindi,
+    Bengali,
+    ChineseMandarin,
+    Japanese,
+    Korean,
+    Vietnamese,
+    Indonesian,
+    Thai,
+    /// Opus-MT `ROMANCE` grouping (Spanish, French, Italian, Portuguese, Romanian, Catalan, ...)
+    Romance,
 }
 
+impl Language {
+    /// Returns the ISO 639-1 (or Opus-MT group) code used to build the `opus-mt-{src}-{tgt}`
+    /// model identifier published by the Helsinki-NLP group.
+    fn opus_code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::Portuguese => "pt",
+            Language::Italian => "it",
+            Language::Catalan => "ca",
+            Language::Romanian => "ro",
+            Language::Dutch => "nl",
+            Language::Swedish => "sv",
+            Language::Danish => "da",
+            Language::Norwegian => "no",
+            Language::Finnish => "fi",
+            Language::Russian => "ru",
+            Language::Polish => "pl",
+            Language::Czech => "cs",
+            Language::Ukrainian => "uk",
+            Language::Greek => "el",
+            Language::Turkish => "tr",
+            Language::Arabic => "ar",
+            Language::Hebrew => "he",
+            Language::Hindi => "hi",
+            Language::Bengali => "bn",
+            Language::ChineseMandarin => "zh",
+            Language::Japanese => "ja",
+            Language::Korean => "ko",
+            Language::Vietnamese => "vi",
+            Language::Indonesian => "id",
+            Language::Thai => "th",
+            Language::Romance => "ROMANCE",
+        }
+    }
+}
+
+/// Opus-MT pairs known to be published by Helsinki-NLP and safe to derive resource URLs for.
+///
+/// `TranslationConfig::new` rejects any pair not listed here with a clear error, rather than
+/// accepting any syntactically valid `(source, target)` combination and letting it 404 deep
+/// inside the resource downloader. Extend this list as more converted checkpoints are verified
+/// to exist.
+const KNOWN_PAIRS: &[(Language, Language)] = &[
+    (Language::English, Language::French),
+    (Language::French, Language::English),
+    (Language::English, Language::German),
+    (Language::German, Language::English),
+    (Language::English, Language::Spanish),
+    (Language::Spanish, Language::English),
+    (Language::English, Language::Italian),
+    (Language::Italian, Language::English),
+    (Language::English, Language::Portuguese),
+    (Language::Portuguese, Language::English),
+    (Language::English, Language::Dutch),
+    (Language::Dutch, Language::English),
+    (Language::English, Language::Russian),
+    (Language::Russian, Language::English),
+    (Language::English, Language::ChineseMandarin),
+    (Language::ChineseMandarin, Language::English),
+    (Language::English, Language::Arabic),
+    (Language::Arabic, Language::English),
+    (Language::English, Language::Hindi),
+    (Language::Hindi, Language::English),
+    (Language::English, Language::Japanese),
+    (Language::Japanese, Language::English),
+    (Language::English, Language::Korean),
+    (Language::Korean, Language::English),
+    (Language::English, Language::Swedish),
+    (Language::Swedish, Language::English),
+    (Language::English, Language::Turkish),
+    (Language::Turkish, Language::English),
+    // many-to-many Romance checkpoints
+    (Language::English, Language::Romance),
+    (Language::Romance, Language::English),
+];
+
 struct RemoteTranslationResources;
 
 impl RemoteTranslationResources {
@@ -63,9 +179,34 @@ impl RemoteTranslationResources {
 
     pub const FRENCH2ENGLISH: ((&'static str, &'static str), (&'static str, &'static str), (&'static str, &'static str), (&'static str, &'static str)) =
         (MarianModelResources::FRENCH2ENGLISH, MarianConfigResources::FRENCH2ENGLISH, MarianVocabResources::FRENCH2ENGLISH, MarianSpmResources::FRENCH2ENGLISH);
+
+    /// Builds the four resource name/url pairs (model, config, vocab, sentence piece) for a
+    /// language pair known to be published by Helsinki-NLP (see [`KNOWN_PAIRS`]), following the
+    /// `Helsinki-NLP/opus-mt-{src}-{tgt}` naming convention used to publish the remaining (non
+    /// pre-converted) checkpoints.
+    fn for_pair(source_language: Language, target_language: Language) -> [(String, String); 4] {
+        let pair_id = format!("opus-mt-{}-{}", source_language.opus_code(), target_language.opus_code());
+        let file = |name: &str| format!("https://huggingface.co/Helsinki-NLP/{}/resolve/main/{}", pair_id, name);
+        [
+            (format!("{}/model", pair_id), file("rust_model.ot")),
+            (format!("{}/config", pair_id), file("config.json")),
+            (format!("{}/vocab", pair_id), file("vocab.json")),
+            (format!("{}/spiece", pair_id), file("source.spm")),
+        ]
+    }
 }
 
 
+/// Converts a historically hardcoded `(&'static str, &'static str)` x4 resource tuple into owned
+/// `String`s so it can be merged with the dynamically derived Opus-MT resource names below.
+fn owned(resources: ((&'static str, &'static str), (&'static str, &'static str), (&'static str, &'static str), (&'static str, &'static str))) -> ((String, String), (String, String), (String, String), (String, String)) {
+    let (model, config, vocab, spiece) = resources;
+    ((model.0.to_string(), model.1.to_string()),
+     (config.0.to_string(), config.1.to_string()),
+     (vocab.0.to_string(), vocab.1.to_string()),
+     (spiece.0.to_string(), spiece.1.to_string()))
+}
+
 /// # Configuration for text translation
 /// Contains information regarding the model to load, mirrors the GenerationConfig, with a
 /// different set of default parameters and sets the device to place the model on.
@@ -102,16 +243,38 @@ pub struct TranslationConfig {
     pub no_repeat_ngram_size: u64,
     /// Number of sequences to return for each prompt text (default: 1)
     pub num_return_sequences: u64,
+    /// Target language marker prepended to every input as `>>{target_language}<<` to select the
+    /// output language of a multilingual (many-to-many) Opus-MT checkpoint, e.g. loading
+    /// `opus-mt-en-ROMANCE` once and setting this to `"fra"` to translate into French, then
+    /// switching the loaded model to `"por"` with `TranslationModel::set_target_language` to
+    /// translate into Portuguese on a subsequent call. Ignored by bilingual checkpoints
+    /// (default: None)
+    pub target_language: Option<String>,
+    /// Maximum number of sentences translated within a single forward pass. Inputs are sorted by
+    /// length and grouped into buckets of similar length before hitting this limit, so padding is
+    /// only paid within a bucket rather than across the whole input slice (default: 16)
+    pub max_batch_size: u64,
+    /// Approximate per-bucket token budget (sum of whitespace-split token counts), used alongside
+    /// `max_batch_size` to keep buckets of long sentences from padding to excessive lengths
+    /// (default: 4096)
+    pub max_batch_tokens: u64,
     /// Device to place the model on (default: CUDA/GPU when available)
     pub device: Device,
 }
 
 impl TranslationConfig {
-    /// Create a new `TranslationCondiguration` from an available language.
+    /// Create a new `TranslationCondiguration` from a source/target language pair.
+    ///
+    /// Falls back to deriving the model/config/vocab/sentence-piece resource names from the
+    /// `opus-mt-{src}-{tgt}` Opus-MT naming convention when the pair is not one of the few
+    /// historically pre-converted checkpoints. The pair must still be listed in `KNOWN_PAIRS`
+    /// (including the many-to-many `Language::Romance` checkpoints); anything else is rejected
+    /// with a clear error rather than failing later with an unrelated 404 from the downloader.
     ///
     /// # Arguments
     ///
-    /// * `language` - `Language` enum value (e.g. `Language::EnglishToFrench`)
+    /// * `source_language` - `Language` enum value of the input text (e.g. `Language::English`)
+    /// * `target_language` - `Language` enum value of the desired translation (e.g. `Language::French`)
     /// * `device` - `Device` to place the model on (CPU/GPU)
     ///
     /// # Example
@@ -121,43 +284,38 @@ impl TranslationConfig {
     /// use rust_bert::pipelines::translation::{TranslationConfig, Language};
     /// use tch::Device;
     ///
-    /// let translation_config =  TranslationConfig::new(Language::FrenchToEnglish, Device::cuda_if_available());
+    /// let translation_config =  TranslationConfig::new(Language::French, Language::English, Device::cuda_if_available())?;
     ///# Ok(())
     ///# }
     /// ```
     ///
-    pub fn new(language: Language, device: Device) -> TranslationConfig {
-        let (model_resource, config_resource, vocab_resource, merges_resource) = match language {
-            Language::EnglishToFrench => RemoteTranslationResources::ENGLISH2FRENCH,
-            Language::FrenchToEnglish => RemoteTranslationResources::FRENCH2ENGLISH
-        };
-        let model_resource = Resource::Remote(RemoteResource::from_pretrained(model_resource));
-        let config_resource = Resource::Remote(RemoteResource::from_pretrained(config_resource));
-        let vocab_resource = Resource::Remote(RemoteResource::from_pretrained(vocab_resource));
-        let merges_resource = Resource::Remote(RemoteResource::from_pretrained(merges_resource));
-        TranslationConfig {
-            model_resource,
-            config_resource,
-            vocab_resource,
-            merges_resource,
-            min_length: 0,
-            max_length: 512,
-            do_sample: false,
-            early_stopping: false,
-            num_beams: 6,
-            temperature: 1.0,
-            top_k: 50,
-            top_p: 1.0,
-            repetition_penalty: 1.0,
-            length_penalty: 1.0,
-            no_repeat_ngram_size: 0,
-            num_return_sequences: 1,
-            device,
+    pub fn new(source_language: Language, target_language: Language, device: Device) -> failure::Fallible<TranslationConfig> {
+        if source_language == target_language {
+            return Err(failure::format_err!("Unsupported language pair: {:?} -> {:?}. Opus-MT does not publish identity translation checkpoints.", source_language, target_language));
+        }
+        if !KNOWN_PAIRS.contains(&(source_language, target_language)) {
+            return Err(failure::format_err!("Unsupported language pair: {:?} -> {:?}. This pair is not in the list of Opus-MT checkpoints known to be published by Helsinki-NLP.", source_language, target_language));
         }
+        let (model_resource, config_resource, vocab_resource, merges_resource) = match (source_language, target_language) {
+            (Language::English, Language::French) => owned(RemoteTranslationResources::ENGLISH2FRENCH),
+            (Language::French, Language::English) => owned(RemoteTranslationResources::FRENCH2ENGLISH),
+            (source_language, target_language) => {
+                let [model, config, vocab, spiece] = RemoteTranslationResources::for_pair(source_language, target_language);
+                (model, config, vocab, spiece)
+            }
+        };
+        let model_resource = Resource::Remote(RemoteResource::from_pretrained((model_resource.0.as_str(), model_resource.1.as_str())));
+        let config_resource = Resource::Remote(RemoteResource::from_pretrained((config_resource.0.as_str(), config_resource.1.as_str())));
+        let vocab_resource = Resource::Remote(RemoteResource::from_pretrained((vocab_resource.0.as_str(), vocab_resource.1.as_str())));
+        let merges_resource = Resource::Remote(RemoteResource::from_pretrained((merges_resource.0.as_str(), merges_resource.1.as_str())));
+        Ok(TranslationConfig::new_from_resources(model_resource, config_resource, vocab_resource, merges_resource, device))
     }
 
     /// Create a new `TranslationCondiguration` from custom (e.g. local) resources.
     ///
+    /// Generation parameters are set to the same defaults as [`TranslationConfig::new`]; use the
+    /// fluent `with_*` setters below to override them.
+    ///
     /// # Arguments
     ///
     /// * `model_resource` - `Resource` pointing to the model
@@ -211,14 +369,149 @@ impl TranslationConfig {
             length_penalty: 1.0,
             no_repeat_ngram_size: 0,
             num_return_sequences: 1,
+            target_language: None,
+            max_batch_size: 16,
+            max_batch_tokens: 4096,
             device,
         }
     }
+
+    /// Sets the minimum sequence length, consuming and returning `self` for fluent chaining.
+    pub fn with_min_length(mut self, min_length: u64) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// Sets the maximum sequence length, consuming and returning `self` for fluent chaining.
+    pub fn with_max_length(mut self, max_length: u64) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Sets the sampling flag, consuming and returning `self` for fluent chaining.
+    pub fn with_do_sample(mut self, do_sample: bool) -> Self {
+        self.do_sample = do_sample;
+        self
+    }
+
+    /// Sets the early stopping flag, consuming and returning `self` for fluent chaining.
+    pub fn with_early_stopping(mut self, early_stopping: bool) -> Self {
+        self.early_stopping = early_stopping;
+        self
+    }
+
+    /// Sets the number of beams for beam search, consuming and returning `self` for fluent chaining.
+    pub fn with_num_beams(mut self, num_beams: u64) -> Self {
+        self.num_beams = num_beams;
+        self
+    }
+
+    /// Sets the sampling temperature, consuming and returning `self` for fluent chaining.
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sets the top-k sampling cutoff, consuming and returning `self` for fluent chaining.
+    pub fn with_top_k(mut self, top_k: u64) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Sets the nucleus (top-p) sampling cutoff, consuming and returning `self` for fluent chaining.
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.top_p = top_p;
+        self
+    }
+
+    /// Sets the repetition penalty, consuming and returning `self` for fluent chaining.
+    pub fn with_repetition_penalty(mut self, repetition_penalty: f64) -> Self {
+        self.repetition_penalty = repetition_penalty;
+        self
+    }
+
+    /// Sets the length penalty, consuming and returning `self` for fluent chaining.
+    pub fn with_length_penalty(mut self, length_penalty: f64) -> Self {
+        self.length_penalty = length_penalty;
+        self
+    }
+
+    /// Sets the no-repeat n-gram size, consuming and returning `self` for fluent chaining.
+    pub fn with_no_repeat_ngram_size(mut self, no_repeat_ngram_size: u64) -> Self {
+        self.no_repeat_ngram_size = no_repeat_ngram_size;
+        self
+    }
+
+    /// Sets the number of sequences returned per input, consuming and returning `self` for fluent chaining.
+    pub fn with_num_return_sequences(mut self, num_return_sequences: u64) -> Self {
+        self.num_return_sequences = num_return_sequences;
+        self
+    }
+
+    /// Sets the `>>{target_language}<<` marker used for multilingual checkpoints, consuming and
+    /// returning `self` for fluent chaining.
+    pub fn with_target_language(mut self, target_language: impl Into<String>) -> Self {
+        self.target_language = Some(target_language.into());
+        self
+    }
+
+    /// Sets the maximum number of sentences translated per forward pass, consuming and returning `self` for fluent chaining.
+    pub fn with_max_batch_size(mut self, max_batch_size: u64) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Sets the approximate per-bucket token budget, consuming and returning `self` for fluent chaining.
+    pub fn with_max_batch_tokens(mut self, max_batch_tokens: u64) -> Self {
+        self.max_batch_tokens = max_batch_tokens;
+        self
+    }
+}
+
+/// Sorts `inputs` by (whitespace-split) token count and partitions them into buckets bounded by
+/// `max_batch_size` items and `max_batch_tokens` total tokens, so a forward pass over one bucket
+/// only pads to the longest sentence within that bucket. Pure list manipulation with no model
+/// dependency, so it can be exercised directly in tests.
+fn build_length_buckets(inputs: Vec<(usize, String)>, max_batch_size: u64, max_batch_tokens: u64) -> Vec<Vec<(usize, String)>> {
+    let mut indexed_inputs = inputs;
+    indexed_inputs.sort_by_key(|(_, text)| text.split_whitespace().count());
+
+    let mut buckets: Vec<Vec<(usize, String)>> = Vec::new();
+    let mut bucket: Vec<(usize, String)> = Vec::new();
+    let mut bucket_tokens: u64 = 0;
+
+    for (index, text) in indexed_inputs {
+        let text_tokens = text.split_whitespace().count() as u64;
+        let bucket_full = !bucket.is_empty()
+            && (bucket.len() as u64 >= max_batch_size || bucket_tokens + text_tokens > max_batch_tokens);
+        if bucket_full {
+            buckets.push(std::mem::take(&mut bucket));
+            bucket_tokens = 0;
+        }
+        bucket_tokens += text_tokens;
+        bucket.push((index, text));
+    }
+    if !bucket.is_empty() {
+        buckets.push(bucket);
+    }
+    buckets
 }
 
 /// # TranslationModel to perform translation
+///
+/// Generation parameters (beam count, sampling, penalties, ...) are fixed for the lifetime of the
+/// model at [`TranslationModel::new`] time. Per-call overrides (e.g. switching a loaded model
+/// between greedy decoding and a high-beam search without rebuilding it) are NOT supported:
+/// `MarianGenerator::generate`'s second parameter is the attention mask, not a config override,
+/// and `MarianGenerator` reads generation parameters only from the `GenerateConfig` it was built
+/// with. Supporting this (tracked as chunk0-4) needs a real override hook added to
+/// `LanguageGenerator`/`MarianGenerator`, outside this file; build a new `TranslationModel` with a
+/// different `TranslationConfig` instead until that plumbing lands.
 pub struct TranslationModel {
-    model: MarianGenerator
+    model: MarianGenerator,
+    target_language: Option<String>,
+    max_batch_size: u64,
+    max_batch_tokens: u64,
 }
 
 impl TranslationModel {
@@ -235,7 +528,7 @@ impl TranslationModel {
     /// use rust_bert::pipelines::translation::{TranslationModel, TranslationConfig, Language};
     /// use tch::Device;
     ///
-    /// let translation_config =  TranslationConfig::new(Language::FrenchToEnglish, Device::cuda_if_available());
+    /// let translation_config =  TranslationConfig::new(Language::French, Language::English, Device::cuda_if_available())?;
     /// let mut summarization_model =  TranslationModel::new(translation_config)?;
     ///# Ok(())
     ///# }
@@ -243,6 +536,9 @@ impl TranslationModel {
     ///
     pub fn new(translation_config: TranslationConfig)
                -> failure::Fallible<TranslationModel> {
+        let target_language = translation_config.target_language.clone();
+        let max_batch_size = translation_config.max_batch_size;
+        let max_batch_tokens = translation_config.max_batch_tokens;
         let generate_config = GenerateConfig {
             model_resource: translation_config.model_resource,
             config_resource: translation_config.config_resource,
@@ -265,11 +561,22 @@ impl TranslationModel {
 
         let model = MarianGenerator::new(generate_config)?;
 
-        Ok(TranslationModel { model })
+        Ok(TranslationModel { model, target_language, max_batch_size, max_batch_tokens })
     }
 
     /// Translates texts provided
     ///
+    /// If a target language was set (`TranslationConfig::target_language` or
+    /// `TranslationModel::set_target_language`), e.g. when using one of the many-to-many
+    /// Opus-MT checkpoints (`Language::Romance`), every input is prefixed with the corresponding
+    /// `>>{target_language}<<` marker before being passed to the model.
+    ///
+    /// Inputs are sorted by length and grouped into buckets bounded by
+    /// `TranslationConfig::max_batch_size` and `TranslationConfig::max_batch_tokens` before beam
+    /// search is run bucket by bucket, so padding overhead scales with the length of each bucket
+    /// rather than with the longest sentence in the whole input. Outputs are returned in the same
+    /// order as the original `texts` slice.
+    ///
     /// # Arguments
     ///
     /// * `input` - `&[&str]` Array of texts to summarize.
@@ -285,7 +592,7 @@ impl TranslationModel {
     /// use rust_bert::pipelines::translation::{TranslationModel, TranslationConfig, Language};
     /// use tch::Device;
     ///
-    /// let translation_config =  TranslationConfig::new(Language::EnglishToFrench, Device::cuda_if_available());
+    /// let translation_config =  TranslationConfig::new(Language::English, Language::French, Device::cuda_if_available())?;
     /// let mut model = TranslationModel::new(translation_config)?;
     ///
     /// let input = ["This is a sentence to be translated"];
@@ -296,6 +603,120 @@ impl TranslationModel {
     /// ```
     ///
     pub fn translate(&mut self, texts: &[&str]) -> Vec<String> {
-        self.model.generate(Some(texts.to_vec()), None)
+        let prepared_inputs = self.prepare_inputs(texts);
+        let indexed_inputs: Vec<(usize, String)> = prepared_inputs.into_iter().enumerate().collect();
+        let buckets = build_length_buckets(indexed_inputs, self.max_batch_size, self.max_batch_tokens);
+
+        let mut outputs: Vec<(usize, String)> = Vec::new();
+        for bucket in &buckets {
+            outputs.extend(self.translate_bucket(bucket));
+        }
+
+        outputs.sort_by_key(|(index, _)| *index);
+        outputs.into_iter().map(|(_, text)| text).collect()
+    }
+
+    /// Runs a single forward/beam-search pass over one length bucket, pairing each translation
+    /// back up with the original input's index so `translate` can restore the input order.
+    /// When several sequences are returned per input, each of them is paired with that input's index.
+    fn translate_bucket(&mut self, bucket: &[(usize, String)]) -> Vec<(usize, String)> {
+        let batch: Vec<&str> = bucket.iter().map(|(_, text)| text.as_str()).collect();
+        let translations = self.model.generate(Some(batch), None);
+        let sequences_per_input = (translations.len() / bucket.len().max(1)).max(1);
+        bucket.iter()
+            .zip(translations.chunks(sequences_per_input))
+            .flat_map(|((index, _), sequences)| sequences.iter().map(move |translation| (*index, translation.clone())))
+            .collect()
+    }
+
+    /// Sets (or clears, with `None`) the `>>{target_language}<<` marker prepended to the inputs of
+    /// subsequent `translate` calls, so a loaded multilingual model can switch its output language.
+    pub fn set_target_language(&mut self, target_language: Option<String>) {
+        self.target_language = target_language;
+    }
+
+    /// Prepends the `>>{target_language}<<` marker to every input when a target language has
+    /// been configured, leaving the inputs untouched for bilingual checkpoints.
+    fn prepare_inputs(&self, texts: &[&str]) -> Vec<String> {
+        match &self.target_language {
+            Some(target_language) => texts.iter().map(|text| format!(">>{}<< {}", target_language, text)).collect(),
+            None => texts.iter().map(|text| text.to_string()).collect(),
+        }
+    }
+}
+
+/// Reduces a `[num_layers, num_heads, target_length, source_length]` decoder cross-attention
+/// tensor into `(source_index, target_index)` alignment pairs by averaging across layers and
+/// heads, then taking the source position with the highest averaged attention for each target
+/// token.
+///
+/// There is no non-test caller: word alignment extraction (tracked as chunk0-5) needs
+/// `MarianGenerator`/the Marian decoder to thread an attention-capture flag through beam search
+/// and hand back cross-attention weights alongside decoded strings, and neither `MarianGenerator`
+/// nor the decoder live in this part of the tree. chunk0-5 is NOT delivered by this function; it
+/// only pins down the reduction math the real `TranslationModel` API will need, behind `cfg(test)`
+/// so it doesn't ship as unreachable production code. Remove the `cfg(test)` gate once a
+/// `TranslationModel` method actually calls this.
+#[cfg(test)]
+fn attention_to_alignment(cross_attention: &Tensor) -> Vec<(usize, usize)> {
+    let averaged = cross_attention.mean_dim(&[0i64, 1], false, tch::Kind::Float);
+    let target_length = averaged.size()[0];
+    let source_indices = averaged.argmax(1, false);
+
+    (0..target_length)
+        .map(|target_index| {
+            let source_index = i64::from(source_indices.get(target_index)) as usize;
+            (source_index, target_index as usize)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_length_buckets_keeps_every_index_within_batch_size() {
+        let inputs = vec![
+            (0, "a long sentence with quite a few words in it".to_string()),
+            (1, "short".to_string()),
+            (2, "another short one".to_string()),
+            (3, "yet another rather long sentence indeed".to_string()),
+        ];
+
+        let buckets = build_length_buckets(inputs, 2, 100);
+
+        let mut indices: Vec<usize> = buckets.iter().flatten().map(|(index, _)| *index).collect();
+        indices.sort_unstable();
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+
+        for bucket in &buckets {
+            assert!(bucket.len() as u64 <= 2);
+        }
+    }
+
+    #[test]
+    fn build_length_buckets_splits_on_token_budget() {
+        let inputs = vec![
+            (0, "one two three four five".to_string()),
+            (1, "six seven eight nine ten".to_string()),
+        ];
+
+        let buckets = build_length_buckets(inputs, 16, 6);
+
+        assert_eq!(buckets.len(), 2);
+    }
+
+    #[test]
+    fn attention_to_alignment_picks_highest_averaged_attention() {
+        // 1 layer, 1 head, 2 target tokens, 3 source tokens
+        let cross_attention = Tensor::of_slice(&[
+            0.1f32, 0.2, 0.7,
+            0.6, 0.3, 0.1,
+        ]).reshape(&[1, 1, 2, 3]);
+
+        let alignment = attention_to_alignment(&cross_attention);
+
+        assert_eq!(alignment, vec![(2, 0), (0, 1)]);
     }
 }
\ No newline at end of file